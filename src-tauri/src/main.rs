@@ -1,45 +1,357 @@
 // Prevents additional console window on Windows in release
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-use tauri::Manager;
-use std::process::{Command, Child};
-use std::sync::Mutex;
+use tauri::{
+    CustomMenuItem, Manager, SystemTray, SystemTrayEvent, SystemTrayMenu, SystemTrayMenuItem,
+};
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::io::{BufRead, BufReader};
+use std::net::TcpListener;
+use std::process::{Command, Child, Stdio};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Maximum number of captured backend log lines kept in memory.
+const LOG_BUFFER_CAPACITY: usize = 1000;
+
+type LogBuffer = Arc<Mutex<VecDeque<String>>>;
+
+#[derive(Clone, Serialize)]
+struct BackendLogEvent {
+    line: String,
+    level: String,
+}
+
+/// Guesses a log level from a line's prefix so the UI can color-code
+/// backend output without the Python side needing a structured log format.
+fn guess_log_level(line: &str) -> String {
+    let trimmed = line.trim_start();
+    let upper = trimmed.to_ascii_uppercase();
+    if upper.starts_with("ERROR") || upper.starts_with("ERR") || upper.starts_with("TRACEBACK") {
+        "error".into()
+    } else if upper.starts_with("WARN") {
+        "warn".into()
+    } else {
+        "info".into()
+    }
+}
 
 struct AppState {
     server_process: Mutex<Option<Child>>,
+    base_url: Mutex<String>,
+    logs: LogBuffer,
+    /// Set while `start_backend` is spawning/polling the backend, so
+    /// `backend_status` can report "starting" instead of "crashed" for the
+    /// window between killing the old process and the new one answering.
+    starting: Mutex<bool>,
+    /// Reason the initial backend start failed, stashed here because
+    /// `setup` runs before the event loop is pumping and can't safely show
+    /// a dialog itself. Drained and shown once `RunEvent::Ready` fires.
+    startup_error: Mutex<Option<String>>,
+}
+
+impl Drop for AppState {
+    fn drop(&mut self) {
+        if let Some(mut child) = self.server_process.lock().unwrap().take() {
+            kill_process_tree(&mut child);
+        }
+    }
+}
+
+/// Binds to an OS-assigned free port, then immediately releases it so the
+/// Python backend can bind to it instead. There's a small window where
+/// another process could grab the port first, but it's good enough for a
+/// local dev server and avoids hardcoding a port two instances could collide on.
+fn find_free_port() -> u16 {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind to a free port");
+    listener.local_addr().expect("Failed to read local addr").port()
+}
+
+/// Spawns the Python backend on `port` with stdout/stderr piped so they can
+/// be captured into `logs` and streamed to the webview instead of just
+/// inheriting the parent's console.
+fn spawn_backend(port: u16, logs: LogBuffer, app: tauri::AppHandle) -> Child {
+    let python = if cfg!(target_os = "windows") { "python" } else { "python3" };
+    let mut child = Command::new(python)
+        .args(["-m", "living_storyworld.cli", "web", "--no-browser", "--port", &port.to_string()])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Failed to start server");
+
+    capture_output(child.stdout.take(), logs.clone(), app.clone());
+    capture_output(child.stderr.take(), logs, app);
+
+    child
+}
+
+/// Drains a piped stdout/stderr handle line-by-line on a background thread,
+/// pushing each line into a bounded ring buffer (so `backend_logs` has
+/// backfill for a newly focused window) and emitting it live to the webview
+/// as a `backend-log` event.
+fn capture_output(pipe: Option<impl std::io::Read + Send + 'static>, logs: LogBuffer, app: tauri::AppHandle) {
+    let Some(pipe) = pipe else { return };
+    std::thread::spawn(move || {
+        let reader = BufReader::new(pipe);
+        for line in reader.lines().map_while(Result::ok) {
+            {
+                let mut logs = logs.lock().unwrap();
+                if logs.len() >= LOG_BUFFER_CAPACITY {
+                    logs.pop_front();
+                }
+                logs.push_back(line.clone());
+            }
+
+            let _ = app.emit_all("backend-log", BackendLogEvent {
+                level: guess_log_level(&line),
+                line,
+            });
+        }
+    });
+}
+
+/// Kills a child process. On Windows, `Child::kill` only terminates the
+/// launcher itself and can leave grandchildren (the actual Python
+/// interpreter spawned via `python -m ...`) running, so we ask `taskkill`
+/// to tear down the whole process tree instead.
+fn kill_process_tree(child: &mut Child) {
+    if cfg!(target_os = "windows") {
+        let _ = Command::new("taskkill")
+            .args(["/PID", &child.id().to_string(), "/T", "/F"])
+            .status();
+    } else {
+        let _ = child.kill();
+    }
+    let _ = child.wait();
+}
+
+/// Issues a bare-bones HTTP/1.0 GET to `/` and checks that *something* comes
+/// back. A successful TCP connect only proves the listener is up, not that
+/// the web app behind it is actually serving requests yet, so we send a real
+/// request rather than trust the socket alone.
+fn http_get_ok(addr: &str) -> bool {
+    use std::io::{Read, Write};
+
+    let Ok(mut stream) = std::net::TcpStream::connect(addr) else {
+        return false;
+    };
+    let _ = stream.set_read_timeout(Some(Duration::from_millis(500)));
+
+    if stream
+        .write_all(b"GET / HTTP/1.0\r\nHost: 127.0.0.1\r\nConnection: close\r\n\r\n")
+        .is_err()
+    {
+        return false;
+    }
+
+    let mut buf = [0u8; 16];
+    matches!(stream.read(&mut buf), Ok(n) if n > 0)
+}
+
+/// Polls the backend until it answers on `/`, the timeout elapses, or the
+/// child process has already exited. Returns Ok(()) once the backend is
+/// ready, or Err with a human-readable reason otherwise.
+fn wait_for_backend_ready(port: u16, child: &mut Child, timeout: Duration) -> Result<(), String> {
+    let deadline = Instant::now() + timeout;
+    let addr = format!("127.0.0.1:{port}");
+
+    while Instant::now() < deadline {
+        if let Some(status) = child.try_wait().map_err(|e| e.to_string())? {
+            return Err(format!("backend process exited early with {status}"));
+        }
+
+        if http_get_ok(&addr) {
+            return Ok(());
+        }
+
+        std::thread::sleep(Duration::from_millis(100));
+    }
+
+    Err(format!("backend did not become ready on {addr} within {timeout:?}"))
+}
+
+/// Spawns the backend on a fresh port and blocks until it is ready,
+/// replacing whatever process/port was previously stored in `state`.
+/// Shared by the initial startup path, the `restart_backend` command, and
+/// the tray's "Restart backend" menu item.
+fn start_backend(state: &AppState, app: tauri::AppHandle) -> Result<(), String> {
+    *state.starting.lock().unwrap() = true;
+    let result = try_start_backend(state, app);
+    *state.starting.lock().unwrap() = false;
+    result
+}
+
+fn try_start_backend(state: &AppState, app: tauri::AppHandle) -> Result<(), String> {
+    let port = find_free_port();
+    let mut server = spawn_backend(port, state.logs.clone(), app);
+
+    if let Err(reason) = wait_for_backend_ready(port, &mut server, Duration::from_secs(15)) {
+        kill_process_tree(&mut server);
+        return Err(reason);
+    }
+
+    *state.server_process.lock().unwrap() = Some(server);
+    *state.base_url.lock().unwrap() = format!("http://127.0.0.1:{port}");
+    Ok(())
+}
+
+#[tauri::command]
+fn backend_url(state: tauri::State<AppState>) -> String {
+    state.base_url.lock().unwrap().clone()
+}
+
+#[tauri::command]
+fn backend_status(state: tauri::State<AppState>) -> String {
+    if *state.starting.lock().unwrap() {
+        return "starting".into();
+    }
+
+    let mut process = state.server_process.lock().unwrap();
+    match process.as_mut() {
+        None => "crashed".into(),
+        Some(child) => match child.try_wait() {
+            Ok(None) => "running".into(),
+            Ok(Some(_)) => "crashed".into(),
+            Err(e) => {
+                eprintln!("failed to poll backend process: {e}");
+                "crashed".into()
+            }
+        },
+    }
+}
+
+#[tauri::command]
+fn restart_backend(app: tauri::AppHandle, state: tauri::State<AppState>) -> Result<(), String> {
+    *state.starting.lock().unwrap() = true;
+    if let Some(mut child) = state.server_process.lock().unwrap().take() {
+        kill_process_tree(&mut child);
+    }
+    start_backend(&state, app)
+}
+
+#[tauri::command]
+fn backend_logs(state: tauri::State<AppState>, lines: usize) -> Vec<String> {
+    let logs = state.logs.lock().unwrap();
+    let skip = logs.len().saturating_sub(lines);
+    logs.iter().skip(skip).cloned().collect()
+}
+
+fn build_tray() -> SystemTray {
+    let menu = SystemTrayMenu::new()
+        .add_item(CustomMenuItem::new("show_hide", "Show/Hide Window"))
+        .add_item(CustomMenuItem::new("restart_backend", "Restart Backend"))
+        .add_item(CustomMenuItem::new("open_browser", "Open Backend URL"))
+        .add_native_item(SystemTrayMenuItem::Separator)
+        .add_item(CustomMenuItem::new("quit", "Quit"));
+
+    SystemTray::new().with_menu(menu)
+}
+
+fn handle_tray_event(app: &tauri::AppHandle, event: SystemTrayEvent) {
+    let SystemTrayEvent::MenuItemClick { id, .. } = event else {
+        return;
+    };
+
+    match id.as_str() {
+        "show_hide" => {
+            if let Some(window) = app.get_window("main") {
+                if window.is_visible().unwrap_or(false) {
+                    let _ = window.hide();
+                } else {
+                    let _ = window.show();
+                    let _ = window.set_focus();
+                }
+            }
+        }
+        "restart_backend" => {
+            // restart_backend blocks for up to the readiness timeout; run it
+            // off the main thread so a slow/failing restart doesn't freeze
+            // the event loop (and the tray itself) while it polls.
+            let app = app.clone();
+            std::thread::spawn(move || {
+                if let Err(reason) = restart_backend(app.clone(), app.state()) {
+                    eprintln!("failed to restart backend: {reason}");
+                }
+            });
+        }
+        "open_browser" => {
+            let url = app.state::<AppState>().base_url.lock().unwrap().clone();
+            let _ = tauri::api::shell::open(&app.shell_scope(), url, None);
+        }
+        "quit" => app.exit(0),
+        _ => {}
+    }
 }
 
 fn main() {
-    tauri::Builder::default()
+    let app = tauri::Builder::default()
+        .invoke_handler(tauri::generate_handler![
+            backend_url,
+            backend_status,
+            restart_backend,
+            backend_logs
+        ])
         .setup(|app| {
-            // Start the Python backend server
-            let server = if cfg!(target_os = "windows") {
-                Command::new("python")
-                    .args(["-m", "living_storyworld.cli", "web", "--no-browser"])
-                    .spawn()
-                    .expect("Failed to start server")
-            } else {
-                Command::new("python3")
-                    .args(["-m", "living_storyworld.cli", "web", "--no-browser"])
-                    .spawn()
-                    .expect("Failed to start server")
-            };
-
             app.manage(AppState {
-                server_process: Mutex::new(Some(server)),
+                server_process: Mutex::new(None),
+                base_url: Mutex::new(String::new()),
+                logs: Arc::new(Mutex::new(VecDeque::new())),
+                starting: Mutex::new(false),
+                startup_error: Mutex::new(None),
             });
 
-            // Give the server time to start
-            std::thread::sleep(std::time::Duration::from_secs(2));
+            let state = app.state::<AppState>();
+            if let Err(reason) = start_backend(&state, app.handle()) {
+                // `dialog::blocking::message` would deadlock here: it waits
+                // on the GTK main loop, which hasn't started pumping yet
+                // during `setup`. Log it immediately so non-Windows launches
+                // get a trace, and stash it so the `RunEvent::Ready` handler
+                // can surface a real dialog once the event loop is up.
+                eprintln!("backend failed to start: {reason}");
+                *state.startup_error.lock().unwrap() = Some(reason);
+            }
 
             Ok(())
         })
-        .on_window_event(|_window, event| {
-            if let tauri::WindowEvent::Destroyed = event {
-                // Kill the server when window closes
-                // This will be handled by Drop trait
+        .system_tray(build_tray())
+        .on_system_tray_event(handle_tray_event)
+        .on_window_event(|window, event| match event {
+            tauri::WindowEvent::CloseRequested { api, .. } => {
+                // Hide to tray instead of destroying the window; only the
+                // tray's Quit item actually exits the app.
+                let _ = window.hide();
+                api.prevent_close();
+            }
+            tauri::WindowEvent::Destroyed => {
+                // Nothing to do here: the backend is killed by the
+                // `RunEvent::ExitRequested`/`Exit` handler in `main`, and by
+                // AppState's Drop impl if the managed state is ever dropped.
             }
+            _ => {}
         })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application");
+
+    app.run(|app_handle, event| match event {
+        tauri::RunEvent::Ready => {
+            let Some(state) = app_handle.try_state::<AppState>() else { return };
+            let Some(reason) = state.startup_error.lock().unwrap().take() else { return };
+
+            tauri::api::dialog::message(
+                app_handle.get_window("main").as_ref(),
+                "Backend failed to start",
+                reason,
+            );
+            app_handle.exit(1);
+        }
+        tauri::RunEvent::ExitRequested { .. } | tauri::RunEvent::Exit => {
+            if let Some(state) = app_handle.try_state::<AppState>() {
+                if let Some(mut child) = state.server_process.lock().unwrap().take() {
+                    kill_process_tree(&mut child);
+                }
+            }
+        }
+        _ => {}
+    });
 }